@@ -0,0 +1,215 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use clap::ValueEnum;
+use mcts::GameState;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::search::{DeterminizedSearch, PersistentSearch, SearchBudget};
+use crate::{Move, NoThanksGame, Player, LOW_CARD};
+
+/// Shared MCTS tuning, threaded through from the CLI so `self_play` and
+/// `with_humans` always honor the same search budget.
+#[derive(Clone, Copy)]
+pub struct SearchConfig {
+    pub ismcts: bool,
+    pub determinizations: usize,
+    pub budget: SearchBudget,
+    pub threads: usize,
+    pub expectiminimax_depth: usize,
+}
+
+/// A seat's decision-making strategy, decoupled from `self_play`/`with_humans`
+/// so new strategies can be added without touching the game loops.
+pub trait Agent {
+    fn choose_move(&mut self, game: &NoThanksGame) -> Move;
+
+    /// Called for every move applied to the game, including moves made by
+    /// other seats and the chance player, so an agent that carries search
+    /// state across turns (see `MctsAgent`) can follow along with what's
+    /// actually been played since it last searched. `game` is the state
+    /// immediately before `mov` is applied. Most agents don't need this.
+    fn observe(&mut self, _game: &NoThanksGame, _mov: &Move) {}
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum AgentKind {
+    Mcts,
+    Expectiminimax,
+}
+
+/// Builds the default agent for `kind`, tuned for interactive/self-play use.
+/// `search` carries both the MCTS budget (`AgentKind::Mcts` only) and the
+/// expectiminimax ply depth (`AgentKind::Expectiminimax` only).
+pub fn make_agent(kind: AgentKind, search: SearchConfig) -> Box<dyn Agent> {
+    match kind {
+        AgentKind::Mcts => Box::new(MctsAgent::new(search)),
+        AgentKind::Expectiminimax => Box::new(ExpectiminimaxAgent::new(search.expectiminimax_depth)),
+    }
+}
+
+/// Either the plain single-tree search or information-set MCTS over the 9
+/// hidden discards, selected by `SearchConfig::ismcts`.
+enum Search {
+    Standard(PersistentSearch),
+    Ismcts(DeterminizedSearch),
+}
+
+pub struct MctsAgent {
+    search: Search,
+    config: SearchConfig,
+    /// Every move applied to the game since this agent's last search, in
+    /// order — including its own previously chosen move. Handed to
+    /// `PersistentSearch::choose_move` so it can confirm the new root really
+    /// descends from the one it searched last time. Cleared after each
+    /// search.
+    pending: Vec<Move>,
+}
+
+impl MctsAgent {
+    pub fn new(config: SearchConfig) -> Self {
+        let search = if config.ismcts {
+            Search::Ismcts(DeterminizedSearch::new())
+        } else {
+            Search::Standard(PersistentSearch::new())
+        };
+        MctsAgent { search, config, pending: Vec::new() }
+    }
+}
+
+impl Agent for MctsAgent {
+    fn choose_move(&mut self, game: &NoThanksGame) -> Move {
+        let mov = match &mut self.search {
+            Search::Standard(search) => {
+                search.choose_move(game.clone(), &self.pending, self.config.budget, self.config.threads)
+            },
+            Search::Ismcts(search) => search.choose_move(
+                game,
+                self.config.determinizations,
+                self.config.budget,
+                self.config.threads,
+            ),
+        };
+        self.pending.clear();
+        mov
+    }
+
+    fn observe(&mut self, _game: &NoThanksGame, mov: &Move) {
+        self.pending.push(mov.clone());
+    }
+}
+
+/// Depth-limited expectiminimax. Only the seat this agent is playing is
+/// actually minimized over; other seats are modeled with a cheap fixed
+/// policy rather than searched, which keeps the game tree tractable.
+///
+/// Chance nodes (`Player::Random`) are searched against a `determinize`d
+/// guess at the 9 face-down discards, the same way `DeterminizedSearch`
+/// handles them for MCTS, rather than `available_moves`' raw un-owned-card
+/// set, so the Random branch in `value` doesn't spend probability mass on
+/// cards that this guess has set aside as discarded.
+pub struct ExpectiminimaxAgent {
+    depth: usize,
+}
+
+impl ExpectiminimaxAgent {
+    pub fn new(depth: usize) -> Self {
+        ExpectiminimaxAgent { depth }
+    }
+
+    /// Projected final scores (lower is better, as in `compute_scores`) for
+    /// `game`, searched `depth` more plies from `player`'s perspective.
+    fn value(&self, game: &NoThanksGame, player: usize, depth: usize) -> Vec<i64> {
+        if game.is_terminal() {
+            return game.compute_scores();
+        }
+        if depth == 0 {
+            return Self::heuristic(game);
+        }
+        match game.current_player() {
+            Player::Random => {
+                let moves = game.available_moves();
+                let n = moves.len() as i64;
+                let mut total = vec![0i64; game.player_tokens.len()];
+                for mov in &moves {
+                    let mut next = game.clone();
+                    next.make_move(mov);
+                    for (t, v) in total.iter_mut().zip(self.value(&next, player, depth - 1)) {
+                        *t += v;
+                    }
+                }
+                total.iter_mut().for_each(|t| *t /= n);
+                total
+            },
+            Player::Player(i) if i == player => {
+                let mut best_value = None;
+                for mov in game.available_moves() {
+                    let mut next = game.clone();
+                    next.make_move(&mov);
+                    let value = self.value(&next, player, depth - 1);
+                    if best_value.as_ref().map_or(true, |best: &Vec<i64>| value[player] < best[player]) {
+                        best_value = Some(value);
+                    }
+                }
+                best_value.unwrap()
+            },
+            Player::Player(_) => {
+                let mut next = game.clone();
+                next.make_move(&Self::fixed_policy(game));
+                self.value(&next, player, depth - 1)
+            },
+        }
+    }
+
+    /// Non-searching opponents take only when they're out of tokens or the
+    /// card's value is no more than its token pile.
+    fn fixed_policy(game: &NoThanksGame) -> Move {
+        if game.player_tokens[game.active_player] == 0 {
+            return Move::Take;
+        }
+        let card_cost = (game.active_card.unwrap() + LOW_CARD) as i64 - game.active_tokens as i64;
+        if card_cost <= 0 {
+            Move::Take
+        } else {
+            Move::Pass
+        }
+    }
+
+    /// `compute_scores` plus a rough accounting for the still-undecided
+    /// `active_card`, since `compute_scores` only counts cards that have
+    /// actually been taken.
+    fn heuristic(game: &NoThanksGame) -> Vec<i64> {
+        let mut scores = game.compute_scores();
+        if let Some(card) = game.active_card {
+            let card_cost = (card + LOW_CARD) as i64 - game.active_tokens as i64;
+            scores[game.active_player] += card_cost;
+        }
+        scores
+    }
+}
+
+impl Agent for ExpectiminimaxAgent {
+    fn choose_move(&mut self, game: &NoThanksGame) -> Move {
+        let player = game.active_player;
+        let game = game.determinize(&mut seeded_rng(game));
+        game.available_moves()
+            .into_iter()
+            .min_by_key(|mov| {
+                let mut next = game.clone();
+                next.make_move(mov);
+                self.value(&next, player, self.depth)[player]
+            })
+            .unwrap()
+    }
+}
+
+/// Derives a reproducible seed from `game`'s own state, so determinizing the
+/// discards for `ExpectiminimaxAgent::choose_move` doesn't turn this agent's
+/// search nondeterministic: the same game state always guesses the same
+/// discards.
+fn seeded_rng(game: &NoThanksGame) -> StdRng {
+    let mut hasher = DefaultHasher::new();
+    Hash::hash(game, &mut hasher);
+    StdRng::seed_from_u64(hasher.finish())
+}
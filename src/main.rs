@@ -4,24 +4,40 @@ use std::fmt;
 
 use rand::prelude::*;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use mcts::*;
 use mcts::tree_policy::*;
 use mcts::transposition_table::*;
 
+mod agent;
+mod record;
+mod search;
+mod tournament;
+
+use std::path::PathBuf;
+
+use agent::{Agent, AgentKind, SearchConfig};
+use record::GameRecorder;
+use search::SearchBudget;
+
 const LOW_CARD: usize = 3;
 const HIGH_CARD: usize = 35;
 const DISCARDED_CARDS: usize = 9;
 const NUM_CARDS: usize = HIGH_CARD - LOW_CARD + 1;
 
 #[derive(Clone, Debug, PartialEq, Hash)]
-struct NoThanksGame {
+pub(crate) struct NoThanksGame {
     active_tokens: usize,
     active_card: Option<usize>,
     active_player: usize,
     cards_taken: usize,
     player_tokens: Vec<usize>,
     card_owners: [Option<usize>; NUM_CARDS],
+    // Cards this determinization has fixed as being among the 9 removed
+    // face-down at setup. Always all-`false` outside of a determinized
+    // search, in which case `available_moves` falls back to treating every
+    // un-owned card as drawable.
+    discarded: [bool; NUM_CARDS],
 }
 
 impl fmt::Display for NoThanksGame {
@@ -55,14 +71,14 @@ impl fmt::Display for NoThanksGame {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-enum Move {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Move {
     Pass,
     Take,
     NextCard(usize),
 }
 
-enum Player {
+pub(crate) enum Player {
     Random,
     Player(usize),
 }
@@ -82,6 +98,7 @@ impl NoThanksGame {
             cards_taken: 0,
             player_tokens: vec![starting_tokens; num_players],
             card_owners: [None; NUM_CARDS],
+            discarded: [false; NUM_CARDS],
         }
     }
 
@@ -89,6 +106,23 @@ impl NoThanksGame {
         self.cards_taken >= NUM_CARDS - DISCARDED_CARDS
     }
 
+    /// Samples one possible assignment of the 9 face-down discards
+    /// consistent with what has been observed so far (i.e. consistent with
+    /// which cards have already been taken or revealed), and returns a clone
+    /// of `self` with those cards marked so `available_moves` excludes them.
+    /// A card that has already been seen can never be sampled as discarded,
+    /// since it's known to still be in the deck or in a player's hand.
+    pub(crate) fn determinize(&self, rng: &mut impl Rng) -> NoThanksGame {
+        let mut game = self.clone();
+        let unseen: Vec<usize> = (0..NUM_CARDS)
+            .filter(|&i| game.card_owners[i].is_none() && Some(i) != game.active_card)
+            .collect();
+        for &card in unseen.choose_multiple(rng, DISCARDED_CARDS) {
+            game.discarded[card] = true;
+        }
+        game
+    }
+
     fn compute_scores(&self) -> Vec<i64> {
         let mut last_owner = None;
         let mut scores: Vec<i64> = self.player_tokens.iter().map(|t| -(*t as i64)).collect();
@@ -129,8 +163,8 @@ impl GameState for NoThanksGame {
                         .iter()
                         .enumerate()
                         .filter_map(|(i, owner)| match owner {
-                            None => Some(Move::NextCard(i)),
-                            Some(_) => None,
+                            None if !self.discarded[i] => Some(Move::NextCard(i)),
+                            _ => None,
                         })
                         .collect()
                 } else {
@@ -176,7 +210,7 @@ impl TranspositionHash for NoThanksGame {
     }
 }
 
-struct MyEvaluator;
+pub(crate) struct MyEvaluator;
 
 impl Evaluator<MyMCTS> for MyEvaluator {
     type StateEvaluation = Vec<i64>; // positive: win by that margin (relative to next player); negative: loss by that margin (relative to first player)
@@ -198,7 +232,7 @@ impl Evaluator<MyMCTS> for MyEvaluator {
 }
 
 #[derive(Default)]
-struct MyMCTS;
+pub(crate) struct MyMCTS;
 
 impl MCTS for MyMCTS {
     type State = NoThanksGame;
@@ -213,6 +247,56 @@ impl MCTS for MyMCTS {
     }
 }
 
+/// MCTS tuning shared by `SelfPlay` and `WithHumans`, so both commands honor
+/// the same search budget.
+#[derive(Args, Clone, Copy)]
+struct SearchArgs {
+    /// Use determinized information-set MCTS instead of letting the Random
+    /// player draw any un-owned card, discards included. Only affects seats
+    /// playing `AgentKind::Mcts`.
+    #[arg(long)]
+    ismcts: bool,
+    /// Must be at least 1: `DeterminizedSearch` divides the playout budget
+    /// evenly across this many determinizations.
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(usize).range(1..))]
+    determinizations: usize,
+    /// Playouts to run before returning a move. Must be at least 1: a zero
+    /// budget would leave `MCTSManager::best_move` with no explored child to
+    /// pick from. Conflicts with `--time-ms`; defaults to 1,000,000 if
+    /// neither is given.
+    #[arg(long, conflicts_with = "time_ms", value_parser = clap::value_parser!(usize).range(1..))]
+    playouts: Option<usize>,
+    /// Wall-clock budget in milliseconds to spend searching, instead of a
+    /// fixed playout count. Gives bounded-latency play instead of move times
+    /// that vary with branching factor and machine speed. Must be at least 1,
+    /// for the same reason as `--playouts`.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    time_ms: Option<u64>,
+    #[arg(long, default_value_t = 8)]
+    threads: usize,
+    /// Ply depth for `AgentKind::Expectiminimax`. Unused by `mcts` agents.
+    #[arg(long, default_value_t = 6)]
+    expectiminimax_depth: usize,
+}
+
+impl SearchArgs {
+    fn config(&self) -> SearchConfig {
+        let budget = match (self.playouts, self.time_ms) {
+            (Some(n), None) => SearchBudget::Playouts(n),
+            (None, Some(ms)) => SearchBudget::TimeMs(ms),
+            (None, None) => SearchBudget::Playouts(1_000_000),
+            (Some(_), Some(_)) => unreachable!("clap enforces --playouts and --time-ms are exclusive"),
+        };
+        SearchConfig {
+            ismcts: self.ismcts,
+            determinizations: self.determinizations,
+            budget,
+            threads: self.threads,
+            expectiminimax_depth: self.expectiminimax_depth,
+        }
+    }
+}
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
@@ -225,29 +309,77 @@ enum Command {
     SelfPlay {
         #[arg(short, long)]
         players: usize,
+        #[command(flatten)]
+        search: SearchArgs,
+        /// Agent for each seat, in seat order. Seats beyond the last value
+        /// given default to `mcts`.
+        #[arg(long, value_enum)]
+        agent: Vec<AgentKind>,
+        /// Save a JSON record of the game to this path, replayable later
+        /// with `Replay`.
+        #[arg(long)]
+        record: Option<PathBuf>,
     },
     WithHumans {
         #[arg(short, long)]
         players: usize,
         #[arg(short, long)]
         which_player: usize,
+        #[command(flatten)]
+        search: SearchArgs,
+        #[arg(long, value_enum)]
+        agent: Option<AgentKind>,
+        #[arg(long)]
+        record: Option<PathBuf>,
+    },
+    /// Reconstructs a game from a JSON record saved by `SelfPlay` or
+    /// `WithHumans` and prints it out move by move.
+    Replay {
+        path: PathBuf,
+    },
+    /// Plays many seeded games between the given agents, rotating seats each
+    /// game, and reports aggregate per-agent performance.
+    Tournament {
+        #[arg(short, long)]
+        players: usize,
+        /// Must be at least 1: `margin_summary` indexes into the per-slot
+        /// margins collected from these games.
+        #[arg(short, long, value_parser = clap::value_parser!(usize).range(1..))]
+        games: usize,
+        #[arg(long)]
+        seed: u64,
+        #[arg(long, value_enum)]
+        agent: Vec<AgentKind>,
+        #[command(flatten)]
+        search: SearchArgs,
     },
 }
 
-fn self_play(players: usize) {
+fn self_play(players: usize, search: SearchArgs, agent_kinds: Vec<AgentKind>, record: Option<PathBuf>) {
     let mut game = NoThanksGame::new(players);
     let mut rng = rand::thread_rng();
+    let search = search.config();
+    let mut agents: Vec<Box<dyn Agent>> = (0..players)
+        .map(|i| {
+            let kind = agent_kinds.get(i).copied().unwrap_or(AgentKind::Mcts);
+            agent::make_agent(kind, search)
+        })
+        .collect();
+    let mut recorder = GameRecorder::new();
     while !game.is_terminal() {
         match game.current_player() {
             Player::Random => {
                 // Choose a random move
-                game.make_move(&game.available_moves().iter().choose(&mut rng).unwrap());
+                let best_move = game.available_moves().iter().choose(&mut rng).unwrap().clone();
+                recorder.observe(&game, &best_move);
+                for agent in agents.iter_mut() {
+                    agent.observe(&game, &best_move);
+                }
+                game.make_move(&best_move);
                 println!("{}", game);
             },
             Player::Player(i) => {
-                let mut mcts = MCTSManager::new(game.clone(), MyMCTS, MyEvaluator, UCTPolicy::new(0.5), ApproxTable::new(1024));
-                mcts.playout_n_parallel(1000000, 8);
-                let best_move = mcts.best_move().unwrap();
+                let best_move = agents[i].choose_move(&game);
                 match best_move {
                     Move::NextCard(_) => panic!("impossible"),
                     Move::Pass => print!("{} passes, ", i),
@@ -255,6 +387,10 @@ fn self_play(players: usize) {
                         println!("{} takes at {} tokens\n", i, game.active_tokens);
                     }
                 }
+                recorder.observe(&game, &best_move);
+                for agent in agents.iter_mut() {
+                    agent.observe(&game, &best_move);
+                }
                 game.make_move(&best_move);
             },
         }
@@ -262,6 +398,10 @@ fn self_play(players: usize) {
 
     println!("{}", game);
     println!("{:?}", game.compute_scores());
+
+    if let Some(path) = record {
+        recorder.finish(players).save(&path).expect("failed to save game record");
+    }
 }
 
 fn get_input_number() -> usize {
@@ -279,24 +419,27 @@ fn get_input_number() -> usize {
     }
 }
 
-fn with_humans(players: usize, which_player: usize) {
+fn with_humans(players: usize, which_player: usize, search: SearchArgs, agent_kind: AgentKind, record: Option<PathBuf>) {
     let mut game = NoThanksGame::new(players);
     let mut game_at_last_card = game.clone();
+    let mut agent = agent::make_agent(agent_kind, search.config());
+    let mut recorder = GameRecorder::new();
     while !game.is_terminal() {
         match game.current_player() {
             Player::Random => {
                 println!("Enter next card:");
                 let card = get_input_number();
-                game.make_move(&Move::NextCard(card - 3));
+                let mov = Move::NextCard(card - 3);
+                recorder.observe(&game, &mov);
+                agent.observe(&game, &mov);
+                game.make_move(&mov);
                 game_at_last_card = game.clone();
                 println!("{}", game);
             }
             Player::Player(i) => {
                 let found_best_move =
                     if i == which_player {
-                        let mut mcts = MCTSManager::new(game.clone(), MyMCTS, MyEvaluator, UCTPolicy::new(0.5), ApproxTable::new(1024));
-                        mcts.playout_n_parallel(1000000, 8);
-                        let best_move = mcts.best_move().unwrap();
+                        let best_move = agent.choose_move(&game);
                         match best_move {
                             Move::NextCard(_) => panic!("impossible"),
                             Move::Pass => {
@@ -319,11 +462,19 @@ fn with_humans(players: usize, which_player: usize) {
                         }
                     };
                 if found_best_move {
+                    // The exact pass/take sequence since the last reveal is
+                    // only known once a human reports how many tokens piled
+                    // up before someone took it, so record it here rather
+                    // than trusting the tentative guess above.
                     println!("When was card taken:");
                     let tokens = get_input_number();
                     while game_at_last_card.active_tokens < tokens {
+                        recorder.observe(&game_at_last_card, &Move::Pass);
+                        agent.observe(&game_at_last_card, &Move::Pass);
                         game_at_last_card.make_move(&Move::Pass);
                     }
+                    recorder.observe(&game_at_last_card, &Move::Take);
+                    agent.observe(&game_at_last_card, &Move::Take);
                     game_at_last_card.make_move(&Move::Take);
                     game = game_at_last_card.clone();
                 }
@@ -333,13 +484,55 @@ fn with_humans(players: usize, which_player: usize) {
 
     println!("{}", game);
     println!("{:?}", game.compute_scores());
+
+    if let Some(path) = record {
+        recorder.finish(players).save(&path).expect("failed to save game record");
+    }
+}
+
+fn tournament(players: usize, games: usize, seed: u64, mut agents: Vec<AgentKind>, search: SearchArgs) {
+    agents.resize(players, AgentKind::Mcts);
+    let config = tournament::TournamentConfig {
+        players,
+        games,
+        seed,
+        agents: agents.clone(),
+        search: search.config(),
+    };
+    let stats = tournament::run(&config);
+    for (kind, stats) in agents.iter().zip(stats.iter()) {
+        let margin = stats.margin_summary();
+        println!(
+            "{:?}: {} games, {:.1}% win rate, {:.2} avg score, {:.2} avg placement, \
+             margin mean {:.2} median {} min {} max {}",
+            kind,
+            stats.games,
+            100.0 * stats.win_rate(),
+            stats.average_score(),
+            stats.average_placement(),
+            margin.mean,
+            margin.median,
+            margin.min,
+            margin.max,
+        );
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Command::SelfPlay { players } => self_play(players),
-        Command::WithHumans { players, which_player } => with_humans(players, which_player),
+        Command::SelfPlay { players, search, agent, record } => {
+            self_play(players, search, agent, record)
+        },
+        Command::WithHumans { players, which_player, search, agent, record } => {
+            with_humans(players, which_player, search, agent.unwrap_or(AgentKind::Mcts), record)
+        },
+        Command::Replay { path } => {
+            record::GameRecord::load(&path).expect("failed to load game record").replay()
+        },
+        Command::Tournament { players, games, seed, agent, search } => {
+            tournament(players, games, seed, agent, search)
+        },
     }
 }
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use mcts::GameState;
+use serde::{Deserialize, Serialize};
+
+use crate::{Move, NoThanksGame, Player};
+
+/// A single player decision, with the token count it was made at so a replay
+/// can sanity-check itself against the reconstructed game state.
+#[derive(Serialize, Deserialize)]
+pub struct RecordedMove {
+    pub player: usize,
+    pub action: Action,
+    pub active_tokens: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Action {
+    Pass,
+    Take,
+}
+
+/// A complete game, serialized as the inputs needed to replay it: the deck
+/// actually dealt (the ordered sequence of `NextCard` reveals) and every
+/// player decision in between. This decouples a game from the live RNG that
+/// produced it, so the same game can be saved, re-run, and shared.
+#[derive(Serialize, Deserialize)]
+pub struct GameRecord {
+    pub num_players: usize,
+    pub deck: Vec<usize>,
+    pub moves: Vec<RecordedMove>,
+}
+
+impl GameRecord {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> io::Result<GameRecord> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Reconstructs the game step by step, printing the state after every
+    /// move (each reveal and each pass or take), then the final scores.
+    pub fn replay(&self) {
+        let mut game = NoThanksGame::new(self.num_players);
+        let mut deck = self.deck.iter();
+        let mut moves = self.moves.iter();
+        while !game.is_terminal() {
+            match game.current_player() {
+                Player::Random => {
+                    let card = *deck.next().expect("deck exhausted before game finished");
+                    game.make_move(&Move::NextCard(card));
+                },
+                Player::Player(_) => {
+                    let recorded = moves.next().expect("moves exhausted before game finished");
+                    if recorded.player != game.active_player {
+                        println!(
+                            "warning: recorded move for player {} but the reconstructed game expects player {}",
+                            recorded.player, game.active_player,
+                        );
+                    }
+                    if recorded.active_tokens != game.active_tokens {
+                        println!(
+                            "warning: recorded {} tokens on the card but the reconstructed game has {}",
+                            recorded.active_tokens, game.active_tokens,
+                        );
+                    }
+                    let mov = match recorded.action {
+                        Action::Pass => Move::Pass,
+                        Action::Take => Move::Take,
+                    };
+                    game.make_move(&mov);
+                },
+            }
+            println!("{}", game);
+        }
+        println!("{:?}", game.compute_scores());
+    }
+}
+
+/// Observes a live game as it's played and accumulates a `GameRecord` that
+/// can be saved once the game ends.
+#[derive(Default)]
+pub struct GameRecorder {
+    deck: Vec<usize>,
+    moves: Vec<RecordedMove>,
+}
+
+impl GameRecorder {
+    pub fn new() -> Self {
+        GameRecorder::default()
+    }
+
+    /// Call immediately before applying `mov` to `game`.
+    pub fn observe(&mut self, game: &NoThanksGame, mov: &Move) {
+        match *mov {
+            Move::NextCard(card) => self.deck.push(card),
+            Move::Pass | Move::Take => {
+                self.moves.push(RecordedMove {
+                    player: game.active_player,
+                    action: if *mov == Move::Take { Action::Take } else { Action::Pass },
+                    active_tokens: game.active_tokens,
+                });
+            },
+        }
+    }
+
+    pub fn finish(self, num_players: usize) -> GameRecord {
+        GameRecord {
+            num_players,
+            deck: self.deck,
+            moves: self.moves,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use rand::prelude::*;
+
+use crate::{Move, MyEvaluator, MyMCTS, NoThanksGame};
+
+/// Capacity of the transposition table backing a `PersistentSearch`. This is
+/// sized for an entire game's worth of accumulated statistics rather than the
+/// single `ApproxTable::new(1024)` the old per-turn search used.
+const TABLE_CAPACITY: usize = 1 << 20;
+
+/// How long a search is allowed to run before it must return its current
+/// best move.
+#[derive(Clone, Copy)]
+pub enum SearchBudget {
+    Playouts(usize),
+    TimeMs(u64),
+}
+
+impl SearchBudget {
+    /// Divides this budget evenly across `n` independent searches, e.g. the
+    /// determinizations of a `DeterminizedSearch`.
+    fn split(self, n: usize) -> SearchBudget {
+        match self {
+            SearchBudget::Playouts(total) => SearchBudget::Playouts(total / n),
+            SearchBudget::TimeMs(total) => SearchBudget::TimeMs(total / n as u64),
+        }
+    }
+}
+
+/// Playouts to run per batch while polling the clock against a `TimeMs`
+/// budget.
+const TIME_BUDGET_BATCH: usize = 10_000;
+
+fn run_budgeted(mcts: &mut MCTSManager<MyMCTS>, budget: SearchBudget, threads: usize) {
+    match budget {
+        SearchBudget::Playouts(playouts) => mcts.playout_n_parallel(playouts, threads),
+        SearchBudget::TimeMs(ms) => {
+            let deadline = Instant::now() + Duration::from_millis(ms);
+            while Instant::now() < deadline {
+                mcts.playout_n_parallel(TIME_BUDGET_BATCH, threads);
+            }
+        },
+    }
+}
+
+/// Runs MCTS for a single seat across an entire game, carrying the
+/// previous turn's search tree forward instead of throwing it away and
+/// starting from zero every time it's this seat's move.
+///
+/// `mcts`'s transposition table is content-addressed (a node is found by
+/// `TranspositionHash`, not by following an owned parent-to-child pointer),
+/// so there's no API to literally detach a child `Node` and splice it in as
+/// the manager's new root. What we do instead is keep the previous turn's
+/// whole `MCTSManager` around and reuse its table: since `NoThanksGame`
+/// hashes its full state, a node explored last turn is found again, already
+/// scored, the moment the new search reaches that same state — which is
+/// exactly the retained-subtree reuse the crate's design intends. Before
+/// trusting the carried-over table, though, `choose_move` replays
+/// `moves_since_last_search` against last turn's own tree, one child lookup
+/// at a time, to confirm the new root really does descend from the old one;
+/// if that walk doesn't resolve (a move we never explored, or a node the
+/// table has since evicted) the table is discarded and rebuilt from scratch
+/// rather than risking an unrelated, collided node's stats leaking in.
+pub struct PersistentSearch {
+    previous: Option<MCTSManager<MyMCTS>>,
+}
+
+impl PersistentSearch {
+    pub fn new() -> Self {
+        PersistentSearch { previous: None }
+    }
+
+    /// Searches from `game` and returns the chosen move. `moves_since_last_search`
+    /// is every move applied since the previous call to `choose_move` on this
+    /// searcher — our own chosen move, then the chance `NextCard` reveal and
+    /// any opponents' pass/take decisions — used to confirm `game` is really
+    /// a descendant of the root searched last time before reusing its tree.
+    pub fn choose_move(
+        &mut self,
+        game: NoThanksGame,
+        moves_since_last_search: &[Move],
+        budget: SearchBudget,
+        threads: usize,
+    ) -> Move {
+        let table = match &self.previous {
+            Some(previous) if descends_from(previous, moves_since_last_search) => previous.table().clone(),
+            _ => ApproxTable::new(TABLE_CAPACITY),
+        };
+        let mut mcts = MCTSManager::new(game, MyMCTS, MyEvaluator, UCTPolicy::new(0.5), table);
+        run_budgeted(&mut mcts, budget, threads);
+        let best_move = mcts.best_move().unwrap();
+        self.previous = Some(mcts);
+        best_move
+    }
+}
+
+/// Walks `previous`'s root down through `moves`, one child lookup at a time,
+/// confirming each move actually matches an explored child at that depth.
+/// Any break in the chain means the carried-over table can't be vouched for.
+fn descends_from(previous: &MCTSManager<MyMCTS>, moves: &[Move]) -> bool {
+    let mut node = previous.tree().root_node();
+    for mov in moves {
+        match node.moves().find(|m| m.get_move() == mov).and_then(|m| m.child()) {
+            Some(child) => node = child,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Information-set MCTS over the hidden 9 discarded cards, by determinizing:
+/// each playout batch is run against a fresh, fully-specified guess at which
+/// cards are face-down, and move statistics are aggregated across many such
+/// guesses rather than trusting any single one.
+pub struct DeterminizedSearch {
+    table: ApproxTable<MyMCTS>,
+}
+
+impl DeterminizedSearch {
+    pub fn new() -> Self {
+        DeterminizedSearch {
+            table: ApproxTable::new(TABLE_CAPACITY),
+        }
+    }
+
+    /// Runs `determinizations` independent searches, each against its own
+    /// sampled discard set and each given an even share of `budget`, and
+    /// returns the move with the most total visits summed across all of
+    /// them.
+    pub fn choose_move(
+        &mut self,
+        game: &NoThanksGame,
+        determinizations: usize,
+        budget: SearchBudget,
+        threads: usize,
+    ) -> Move {
+        let per_determinization = budget.split(determinizations);
+        let mut rng = rand::thread_rng();
+        let mut visits: HashMap<Move, u32> = HashMap::new();
+        for _ in 0..determinizations {
+            let sampled = game.determinize(&mut rng);
+            let mut mcts = MCTSManager::new(
+                sampled,
+                MyMCTS,
+                MyEvaluator,
+                UCTPolicy::new(0.5),
+                self.table.clone(),
+            );
+            run_budgeted(&mut mcts, per_determinization, threads);
+            for move_info in mcts.tree().root_node().moves() {
+                *visits.entry(move_info.get_move().clone()).or_insert(0) += move_info.visits();
+            }
+            self.table = mcts.table().clone();
+        }
+        visits
+            .into_iter()
+            .max_by_key(|&(_, v)| v)
+            .map(|(mov, _)| mov)
+            .unwrap()
+    }
+}
@@ -0,0 +1,142 @@
+use mcts::GameState;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::agent::{make_agent, Agent, AgentKind, SearchConfig};
+use crate::{Move, NoThanksGame, Player, DISCARDED_CARDS, NUM_CARDS};
+
+pub struct TournamentConfig {
+    pub players: usize,
+    pub games: usize,
+    pub seed: u64,
+    /// One agent kind per seat "slot". Seats rotate which slot they play
+    /// across games to remove position bias.
+    pub agents: Vec<AgentKind>,
+    pub search: SearchConfig,
+}
+
+#[derive(Default, Clone)]
+pub struct AgentStats {
+    pub games: u64,
+    pub wins: u64,
+    pub total_score: i64,
+    pub total_placement: u64,
+    pub margins: Vec<i64>,
+}
+
+impl AgentStats {
+    pub fn average_score(&self) -> f64 {
+        self.total_score as f64 / self.games as f64
+    }
+
+    pub fn average_placement(&self) -> f64 {
+        self.total_placement as f64 / self.games as f64
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games as f64
+    }
+
+    /// Summarizes `margins` (this slot's score minus the best score, each
+    /// game it played) as mean/median/min/max.
+    pub fn margin_summary(&self) -> MarginSummary {
+        let mut margins = self.margins.clone();
+        margins.sort_unstable();
+        let mean = margins.iter().sum::<i64>() as f64 / margins.len() as f64;
+        MarginSummary {
+            mean,
+            median: margins[margins.len() / 2],
+            min: margins[0],
+            max: *margins.last().unwrap(),
+        }
+    }
+}
+
+pub struct MarginSummary {
+    pub mean: f64,
+    pub median: i64,
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Plays `config.games` complete, seeded games and returns per-slot stats.
+/// Each deck (the 24 dealt cards and their order) is pre-generated from the
+/// seeded RNG and dealt for `config.players` consecutive games, one per seat
+/// rotation, so every slot assignment faces identical deals, and slots rotate
+/// across seats each game to cancel out any seat-position advantage. When
+/// `config.games` is not a multiple of `config.players`, the last deck is
+/// dealt in fewer rotations.
+pub fn run(config: &TournamentConfig) -> Vec<AgentStats> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut stats = vec![AgentStats::default(); config.agents.len()];
+
+    let mut deck = Vec::new();
+    for game_index in 0..config.games {
+        let rotation = game_index % config.players;
+        if rotation == 0 {
+            deck = sample_deck(&mut rng);
+        }
+        let mut game = NoThanksGame::new(config.players);
+        let mut agents: Vec<_> = (0..config.players)
+            .map(|seat| {
+                make_agent(
+                    config.agents[slot_for_seat(seat, rotation, config.players)],
+                    config.search,
+                )
+            })
+            .collect();
+
+        let mut deck = deck.iter().copied();
+        while !game.is_terminal() {
+            match game.current_player() {
+                Player::Random => {
+                    let mov = Move::NextCard(deck.next().expect("deck exhausted"));
+                    for agent in agents.iter_mut() {
+                        agent.observe(&game, &mov);
+                    }
+                    game.make_move(&mov);
+                },
+                Player::Player(seat) => {
+                    let mov = agents[seat].choose_move(&game);
+                    for agent in agents.iter_mut() {
+                        agent.observe(&game, &mov);
+                    }
+                    game.make_move(&mov);
+                },
+            }
+        }
+
+        let scores = game.compute_scores();
+        let best_score = *scores.iter().min().unwrap();
+
+        for seat in 0..config.players {
+            let slot = slot_for_seat(seat, rotation, config.players);
+            // Competition ranking: a seat's placement is 1 + the number of
+            // seats that strictly beat it, so every seat tied for the best
+            // score places first (and wins) instead of only whichever one
+            // happened to sort there.
+            let placement = 1 + scores.iter().filter(|&&s| s < scores[seat]).count() as u64;
+            let entry = &mut stats[slot];
+            entry.games += 1;
+            entry.total_score += scores[seat];
+            entry.total_placement += placement;
+            entry.margins.push(scores[seat] - best_score);
+            if scores[seat] == best_score {
+                entry.wins += 1;
+            }
+        }
+    }
+
+    stats
+}
+
+fn slot_for_seat(seat: usize, rotation: usize, players: usize) -> usize {
+    (seat + rotation) % players
+}
+
+fn sample_deck(rng: &mut StdRng) -> Vec<usize> {
+    let mut cards: Vec<usize> = (0..NUM_CARDS).collect();
+    cards.shuffle(rng);
+    cards.truncate(NUM_CARDS - DISCARDED_CARDS);
+    cards
+}